@@ -1,30 +1,110 @@
+use chrono::Utc;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use serde::{Deserialize, Serialize};
-use std::fs;
+use std::collections::HashSet;
 use std::path::PathBuf;
 use std::sync::Mutex;
-use tauri::Manager;
+use tauri::{Emitter, Manager};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use tracing::{error, warn};
 
 // ============================================================
 // Settings (User Preferences)
 // ============================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
     #[serde(rename = "customDataPath", skip_serializing_if = "Option::is_none")]
     pub custom_data_path: Option<String>,
+    #[serde(rename = "maxBackups", default = "default_max_backups")]
+    pub max_backups: u32,
+    #[serde(rename = "profiles", default)]
+    pub profiles: Vec<Profile>,
+    #[serde(rename = "activeProfile", skip_serializing_if = "Option::is_none")]
+    pub active_profile: Option<String>,
+}
+
+fn default_max_backups() -> u32 {
+    10
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            custom_data_path: None,
+            max_backups: default_max_backups(),
+            profiles: Vec::new(),
+            active_profile: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    #[serde(rename = "dataPath")]
+    pub data_path: String,
+    #[serde(rename = "weeklyHourGoalOverride", skip_serializing_if = "Option::is_none")]
+    pub weekly_hour_goal_override: Option<f64>,
+}
+
+impl Settings {
+    fn find_active_profile(&self) -> Option<&Profile> {
+        let name = self.active_profile.as_ref()?;
+        self.profiles.iter().find(|p| &p.name == name)
+    }
+}
+
+// ============================================================
+// Layered configuration (base settings + active profile)
+// ============================================================
+
+trait Merge {
+    /// Fields set on `other` win; unset fields fall back to `self`.
+    fn merge(self, other: Self) -> Self;
+}
+
+#[derive(Debug, Clone, Default)]
+struct EffectiveConfig {
+    data_path: Option<String>,
+    weekly_hour_goal: Option<f64>,
+}
+
+impl Merge for EffectiveConfig {
+    fn merge(self, other: Self) -> Self {
+        Self {
+            data_path: other.data_path.or(self.data_path),
+            weekly_hour_goal: other.weekly_hour_goal.or(self.weekly_hour_goal),
+        }
+    }
+}
+
+fn resolve_effective_config(settings: &Settings) -> EffectiveConfig {
+    let base = EffectiveConfig {
+        data_path: settings.custom_data_path.clone(),
+        weekly_hour_goal: None,
+    };
+    match settings.find_active_profile() {
+        Some(profile) => base.merge(EffectiveConfig {
+            data_path: Some(profile.data_path.clone()),
+            weekly_hour_goal: profile.weekly_hour_goal_override,
+        }),
+        None => base,
+    }
 }
 
 // ============================================================
 // Data Models
 // ============================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ChargeCodeSplit {
     pub code: String,
     pub percentage: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Project {
     pub id: String,
     pub name: String,
@@ -37,7 +117,7 @@ pub struct Project {
     pub charge_code_splits: Option<Vec<ChargeCodeSplit>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TimeBlock {
     pub id: String,
     #[serde(rename = "projectId")]
@@ -47,7 +127,7 @@ pub struct TimeBlock {
     pub slot_index: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WeekData {
     #[serde(rename = "weekKey")]
     pub week_key: String,
@@ -56,7 +136,7 @@ pub struct WeekData {
     pub blocks: Vec<TimeBlock>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TemplateBlock {
     #[serde(rename = "projectId")]
     pub project_id: String,
@@ -65,26 +145,35 @@ pub struct TemplateBlock {
     pub slot_index: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Template {
     pub id: String,
     pub name: String,
     pub blocks: Vec<TemplateBlock>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AppData {
     pub projects: Vec<Project>,
     pub weeks: Vec<WeekData>,
     pub templates: Vec<Template>,
     #[serde(rename = "weeklyHourGoal", default = "default_weekly_goal")]
     pub weekly_hour_goal: f64,
+    #[serde(rename = "schemaVersion", default)]
+    pub schema_version: u32,
 }
 
 fn default_weekly_goal() -> f64 {
     40.0
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub path: String,
+    pub timestamp: String,
+    pub size: u64,
+}
+
 impl Default for AppData {
     fn default() -> Self {
         Self {
@@ -92,6 +181,7 @@ impl Default for AppData {
             weeks: Vec::new(),
             templates: Vec::new(),
             weekly_hour_goal: 40.0,
+            schema_version: CURRENT_SCHEMA_VERSION,
         }
     }
 }
@@ -105,29 +195,49 @@ pub struct AppState {
     pub data_path: Mutex<PathBuf>,
     pub settings: Mutex<Settings>,
     pub app_handle: tauri::AppHandle,
+    pub watcher: Mutex<Option<RecommendedWatcher>>,
+    /// Serializes `reconcile_external_change` so overlapping filesystem
+    /// events (common with editors/sync clients that emit several events per
+    /// logical write) can't both read the same stale memory snapshot and
+    /// race to `save_data`, clobbering each other's merge.
+    pub reconcile_lock: tokio::sync::Mutex<()>,
+}
+
+/// Ties a loaded value to the file it was read from, so callers that juggle
+/// more than one data file (profiles, backups) don't lose track of provenance.
+struct WithPath<T> {
+    value: T,
+    path: PathBuf,
+}
+
+async fn load_data_with_path(path: PathBuf, max_backups: u32) -> Result<WithPath<AppData>, String> {
+    let value = load_data(&path, max_backups).await?;
+    Ok(WithPath { value, path })
 }
 
-fn get_config_path(app: &tauri::AppHandle) -> PathBuf {
+async fn get_config_path(app: &tauri::AppHandle) -> PathBuf {
     let config_dir = app
         .path()
         .app_config_dir()
         .expect("Failed to get app config directory");
-    fs::create_dir_all(&config_dir).expect("Failed to create app config directory");
+    fs::create_dir_all(&config_dir)
+        .await
+        .expect("Failed to create app config directory");
     config_dir.join("settings.json")
 }
 
-fn load_settings(path: &PathBuf) -> Settings {
-    if path.exists() {
-        match fs::read_to_string(path) {
+async fn load_settings(path: &PathBuf) -> Settings {
+    if fs::metadata(path).await.is_ok() {
+        match fs::read_to_string(path).await {
             Ok(contents) => match serde_json::from_str(&contents) {
                 Ok(settings) => settings,
                 Err(e) => {
-                    eprintln!("Failed to parse settings file: {}", e);
+                    error!("Failed to parse settings file: {}", e);
                     Settings::default()
                 }
             },
             Err(e) => {
-                eprintln!("Failed to read settings file: {}", e);
+                error!("Failed to read settings file: {}", e);
                 Settings::default()
             }
         }
@@ -136,115 +246,864 @@ fn load_settings(path: &PathBuf) -> Settings {
     }
 }
 
-fn save_settings_file(path: &PathBuf, settings: &Settings) -> Result<(), String> {
+async fn save_settings_file(path: &PathBuf, settings: &Settings) -> Result<(), String> {
     let json = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
-    fs::write(path, json).map_err(|e| e.to_string())?;
+    fs::write(path, json).await.map_err(|e| e.to_string())?;
     Ok(())
 }
 
-fn get_default_data_path(app: &tauri::AppHandle) -> PathBuf {
+async fn get_default_data_path(app: &tauri::AppHandle) -> PathBuf {
     let app_dir = app
         .path()
         .app_data_dir()
         .expect("Failed to get app data directory");
-    fs::create_dir_all(&app_dir).expect("Failed to create app data directory");
+    fs::create_dir_all(&app_dir)
+        .await
+        .expect("Failed to create app data directory");
     app_dir.join("time_manager_data.json")
 }
 
-fn get_data_path(app: &tauri::AppHandle, settings: &Settings) -> PathBuf {
-    if let Some(custom_path) = &settings.custom_data_path {
-        let custom_dir = PathBuf::from(custom_path);
-        if custom_dir.exists() && custom_dir.is_dir() {
-            return custom_dir.join("time_manager_data.json");
-        } else {
-            eprintln!("Custom data path does not exist or is not a directory: {}", custom_path);
+async fn get_data_path(app: &tauri::AppHandle, settings: &Settings) -> PathBuf {
+    let effective = resolve_effective_config(settings);
+    if let Some(data_path) = &effective.data_path {
+        let dir = PathBuf::from(data_path);
+        match fs::metadata(&dir).await {
+            Ok(meta) if meta.is_dir() => return dir.join("time_manager_data.json"),
+            _ => {
+                warn!(
+                    "Configured data path does not exist or is not a directory: {}",
+                    data_path
+                );
+            }
         }
     }
-    get_default_data_path(app)
+    get_default_data_path(app).await
 }
 
-fn load_data(path: &PathBuf) -> AppData {
-    if path.exists() {
-        match fs::read_to_string(path) {
-            Ok(contents) => match serde_json::from_str(&contents) {
-                Ok(data) => data,
-                Err(e) => {
-                    eprintln!("Failed to parse data file: {}", e);
-                    AppData::default()
+// ============================================================
+// Schema Migrations
+// ============================================================
+
+/// Bump whenever a migration step below is added; `AppData::schema_version`
+/// written to disk always matches this once a file has been loaded once.
+const CURRENT_SCHEMA_VERSION: u32 = 2;
+
+type Migration = fn(serde_json::Value) -> serde_json::Value;
+
+/// Ordered migrations, index `i` taking a file from version `i` to `i + 1`.
+const MIGRATIONS: &[Migration] = &[migrate_add_color_index, migrate_split_charge_code];
+
+fn migrate_add_color_index(mut value: serde_json::Value) -> serde_json::Value {
+    if let Some(projects) = value.get_mut("projects").and_then(|p| p.as_array_mut()) {
+        for (index, project) in projects.iter_mut().enumerate() {
+            if let Some(obj) = project.as_object_mut() {
+                obj.entry("colorIndex")
+                    .or_insert_with(|| serde_json::json!(index as u32 % 8));
+            }
+        }
+    }
+    value
+}
+
+fn migrate_split_charge_code(mut value: serde_json::Value) -> serde_json::Value {
+    if let Some(projects) = value.get_mut("projects").and_then(|p| p.as_array_mut()) {
+        for project in projects.iter_mut() {
+            if let Some(obj) = project.as_object_mut() {
+                if let Some(code) = obj.remove("chargeCode").and_then(|c| c.as_str().map(String::from)) {
+                    if !code.is_empty() {
+                        obj.insert(
+                            "chargeCodeSplits".to_string(),
+                            serde_json::json!([{ "code": code, "percentage": 100.0 }]),
+                        );
+                    }
                 }
+            }
+        }
+    }
+    value
+}
+
+/// Runs the on-disk JSON through every migration needed to reach
+/// `CURRENT_SCHEMA_VERSION`, snapshotting the pre-migration file into
+/// `backups/` and writing the upgraded file back atomically.
+async fn migrate_data(
+    path: &PathBuf,
+    mut value: serde_json::Value,
+    on_disk_version: u32,
+    max_backups: u32,
+) -> Result<serde_json::Value, String> {
+    for migration in &MIGRATIONS[on_disk_version as usize..] {
+        value = migration(value);
+    }
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert(
+            "schemaVersion".to_string(),
+            serde_json::json!(CURRENT_SCHEMA_VERSION),
+        );
+    }
+
+    rotate_backup(path, max_backups).await?;
+    let migrated_json = serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?;
+    atomic_write(path, &migrated_json).await?;
+
+    Ok(value)
+}
+
+/// Returns the `schemaVersion` recorded in `value` (defaulting to 0 for
+/// pre-migration files), or an error if it's newer than this build
+/// understands. Shared by `load_data` and `restore_backup` so neither can
+/// treat a too-new or not-yet-migrated file as current.
+fn check_schema_version(value: &serde_json::Value) -> Result<u32, String> {
+    let on_disk_version = value
+        .get("schemaVersion")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as u32;
+
+    if on_disk_version > CURRENT_SCHEMA_VERSION {
+        return Err(format!(
+            "Data file schema version {} is newer than the {} this build supports; refusing to load or overwrite it",
+            on_disk_version, CURRENT_SCHEMA_VERSION
+        ));
+    }
+
+    Ok(on_disk_version)
+}
+
+async fn load_data(path: &PathBuf, max_backups: u32) -> Result<AppData, String> {
+    if fs::metadata(path).await.is_err() {
+        return Ok(AppData::default());
+    }
+
+    let contents = match fs::read_to_string(path).await {
+        Ok(contents) => contents,
+        Err(e) => {
+            error!("Failed to read data file: {}", e);
+            return Ok(AppData::default());
+        }
+    };
+
+    let mut value: serde_json::Value = match serde_json::from_str(&contents) {
+        Ok(value) => value,
+        Err(e) => {
+            error!("Failed to parse data file: {}", e);
+            return Ok(AppData::default());
+        }
+    };
+
+    let on_disk_version = check_schema_version(&value)?;
+
+    if on_disk_version < CURRENT_SCHEMA_VERSION {
+        value = migrate_data(path, value, on_disk_version, max_backups).await?;
+    }
+
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+fn backups_dir(data_path: &PathBuf) -> PathBuf {
+    data_path
+        .parent()
+        .map(|dir| dir.join("backups"))
+        .unwrap_or_else(|| PathBuf::from("backups"))
+}
+
+async fn atomic_write(path: &PathBuf, contents: &str) -> Result<(), String> {
+    let mut tmp_name = path.as_os_str().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_name);
+
+    let mut file = fs::File::create(&tmp_path).await.map_err(|e| e.to_string())?;
+    file.write_all(contents.as_bytes())
+        .await
+        .map_err(|e| e.to_string())?;
+    file.sync_all().await.map_err(|e| e.to_string())?;
+    drop(file);
+
+    fs::rename(&tmp_path, path).await.map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+async fn rotate_backup(data_path: &PathBuf, max_backups: u32) -> Result<(), String> {
+    if fs::metadata(data_path).await.is_err() {
+        return Ok(());
+    }
+
+    let dir = backups_dir(data_path);
+    fs::create_dir_all(&dir).await.map_err(|e| e.to_string())?;
+
+    let timestamp = Utc::now().format("%Y%m%dT%H%M%S%.3fZ").to_string();
+    let file_name = data_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("time_manager_data.json");
+    let backup_path = dir.join(format!("{}.{}", timestamp, file_name));
+    fs::copy(data_path, &backup_path)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    prune_backups(&dir, max_backups).await
+}
+
+async fn prune_backups(dir: &PathBuf, max_backups: u32) -> Result<(), String> {
+    let mut entries = fs::read_dir(dir).await.map_err(|e| e.to_string())?;
+    let mut files = Vec::new();
+    while let Some(entry) = entries.next_entry().await.map_err(|e| e.to_string())? {
+        if entry.metadata().await.map(|m| m.is_file()).unwrap_or(false) {
+            files.push(entry.path());
+        }
+    }
+    files.sort();
+
+    while files.len() > max_backups as usize {
+        let oldest = files.remove(0);
+        if let Err(e) = fs::remove_file(&oldest).await {
+            warn!("Failed to prune old backup {}: {}", oldest.display(), e);
+        }
+    }
+    Ok(())
+}
+
+async fn save_data(path: &PathBuf, data: &AppData, max_backups: u32) -> Result<(), String> {
+    rotate_backup(path, max_backups).await?;
+    let json = serde_json::to_string_pretty(data).map_err(|e| e.to_string())?;
+    atomic_write(path, &json).await
+}
+
+// ============================================================
+// External File Watching & Reconciliation
+// ============================================================
+
+/// Unions two entity slices keyed by `key`, letting `disk` win on shared keys
+/// and keeping entries that only exist on one side.
+fn merge_by_id<T, F>(memory: &[T], disk: &[T], key: F) -> Vec<T>
+where
+    T: Clone,
+    F: Fn(&T) -> String,
+{
+    let disk_keys: HashSet<String> = disk.iter().map(&key).collect();
+    let mut merged: Vec<T> = disk.to_vec();
+    for item in memory {
+        if !disk_keys.contains(&key(item)) {
+            merged.push(item.clone());
+        }
+    }
+    merged
+}
+
+fn merge_weeks(memory: &[WeekData], disk: &[WeekData]) -> Vec<WeekData> {
+    let mut merged = Vec::new();
+    let mut seen = HashSet::new();
+
+    for disk_week in disk {
+        seen.insert(disk_week.week_key.clone());
+        let merged_week = match memory.iter().find(|w| w.week_key == disk_week.week_key) {
+            Some(memory_week) => WeekData {
+                week_key: disk_week.week_key.clone(),
+                start_date: disk_week.start_date.clone(),
+                blocks: merge_by_id(&memory_week.blocks, &disk_week.blocks, |b| b.id.clone()),
             },
-            Err(e) => {
-                eprintln!("Failed to read data file: {}", e);
-                AppData::default()
+            None => disk_week.clone(),
+        };
+        merged.push(merged_week);
+    }
+    for memory_week in memory {
+        if !seen.contains(&memory_week.week_key) {
+            merged.push(memory_week.clone());
+        }
+    }
+    merged
+}
+
+fn merge_app_data(memory: &AppData, disk: &AppData) -> AppData {
+    AppData {
+        projects: merge_by_id(&memory.projects, &disk.projects, |p| p.id.clone()),
+        weeks: merge_weeks(&memory.weeks, &disk.weeks),
+        templates: merge_by_id(&memory.templates, &disk.templates, |t| t.id.clone()),
+        weekly_hour_goal: disk.weekly_hour_goal,
+        schema_version: disk.schema_version,
+    }
+}
+
+/// Reloads the data file after an external edit, merges it with whatever is
+/// currently in memory, persists the reconciled result, and tells the
+/// frontend to refresh.
+async fn reconcile_external_change(app: &tauri::AppHandle) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let _reconcile_guard = state.reconcile_lock.lock().await;
+    let data_path = lock_recover(&state.data_path).clone();
+    let max_backups = lock_recover(&state.settings).max_backups;
+    let disk_data = load_data(&data_path, max_backups).await?;
+
+    let memory_data = lock_recover(&state.data).clone();
+    if disk_data == memory_data {
+        // The file event was the app's own write (save, backup rotation,
+        // export) rather than an external edit. Reconciling anyway would
+        // rewrite the file, retriggering the watcher and looping forever.
+        return Ok(());
+    }
+
+    let merged = merge_app_data(&memory_data, &disk_data);
+
+    {
+        let mut data = lock_recover(&state.data);
+        *data = merged.clone();
+    }
+
+    save_data(&data_path, &merged, max_backups).await?;
+
+    app.emit("data-changed", ()).map_err(|e| e.to_string())
+}
+
+fn spawn_watcher(app: tauri::AppHandle) -> RecommendedWatcher {
+    notify::recommended_watcher(move |res: notify::Result<Event>| {
+        let Ok(event) = res else { return };
+        if !matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_)) {
+            return;
+        }
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = reconcile_external_change(&app).await {
+                error!("Failed to reconcile external data change: {}", e);
             }
+        });
+    })
+    .expect("Failed to create file watcher")
+}
+
+/// (Re)points the background watcher at `data_path`'s directory. Called at
+/// startup and whenever the active data file changes (custom location,
+/// reset to default, profile switch).
+fn rewatch(state: &AppState, data_path: &PathBuf) {
+    let mut watcher = spawn_watcher(state.app_handle.clone());
+    if let Some(dir) = data_path.parent() {
+        if let Err(e) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+            warn!("Failed to watch data directory {}: {}", dir.display(), e);
         }
+    }
+    *lock_recover(&state.watcher) = Some(watcher);
+}
+
+// ============================================================
+// Export (iCalendar / CSV)
+// ============================================================
+
+/// Minutes covered by a single schedule slot; `TimeBlock.slot_index` counts
+/// slots of this length from midnight on the block's day.
+const SLOT_MINUTES: i64 = 30;
+
+const WEEK_DAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+fn slot_start(start_date: &str, day: &str, slot_index: u32) -> Result<chrono::NaiveDateTime, String> {
+    let base = chrono::NaiveDate::parse_from_str(start_date, "%Y-%m-%d")
+        .map_err(|e| format!("Invalid start date '{}': {}", start_date, e))?;
+    let offset = WEEK_DAYS
+        .iter()
+        .position(|d| *d == day)
+        .ok_or_else(|| format!("Unknown day: {}", day))? as i64;
+    let date = base + chrono::Duration::days(offset);
+    let midnight = date
+        .and_hms_opt(0, 0, 0)
+        .ok_or_else(|| "Failed to build midnight timestamp".to_string())?;
+    Ok(midnight + chrono::Duration::minutes(slot_index as i64 * SLOT_MINUTES))
+}
+
+fn ics_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace(';', "\\;")
+        .replace('\n', "\\n")
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains(',') || field.contains('"') || field.contains('\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
     } else {
-        AppData::default()
+        field.to_string()
     }
 }
 
-fn save_data(path: &PathBuf, data: &AppData) -> Result<(), String> {
-    let json = serde_json::to_string_pretty(data).map_err(|e| e.to_string())?;
-    fs::write(path, json).map_err(|e| e.to_string())?;
-    Ok(())
+/// Writes export output under `<data dir>/exports/<file_name>` via the
+/// atomic-write path, so a failed export never leaves a truncated file.
+async fn write_export(data_path: &PathBuf, file_name: &str, contents: &str) -> Result<(), String> {
+    let dir = data_path
+        .parent()
+        .map(|d| d.join("exports"))
+        .ok_or_else(|| "Failed to resolve export directory".to_string())?;
+    fs::create_dir_all(&dir).await.map_err(|e| e.to_string())?;
+    atomic_write(&dir.join(file_name), contents).await
 }
 
 // ============================================================
 // Tauri Commands
 // ============================================================
 
+/// Acquires a mutex, recovering the guard if a prior panic poisoned it
+/// rather than letting that poisoning brick every command after it.
+fn lock_recover<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Runs `f` with `std::panic::catch_unwind`, turning a panic into an
+/// `Err(String)` so a single bad payload can't take down the command surface.
+fn catch_sync<F, T>(f: F) -> Result<T, String>
+where
+    F: FnOnce() -> Result<T, String>,
+{
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
+        Ok(result) => result,
+        Err(payload) => {
+            let message = panic_message(&payload);
+            error!("Command panicked: {}", message);
+            Err(format!("Internal error: {}", message))
+        }
+    }
+}
+
+/// Async counterpart of [`catch_sync`] for `#[tauri::command]` bodies that
+/// await I/O: a panic anywhere in `fut`, including across `.await` points,
+/// becomes an `Err(String)` instead of propagating and poisoning state.
+async fn catch_async<F, T>(fut: F) -> Result<T, String>
+where
+    F: std::future::Future<Output = Result<T, String>>,
+{
+    use futures::FutureExt;
+    match std::panic::AssertUnwindSafe(fut).catch_unwind().await {
+        Ok(result) => result,
+        Err(payload) => {
+            let message = panic_message(&payload);
+            error!("Command panicked: {}", message);
+            Err(format!("Internal error: {}", message))
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        (*s).to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+async fn persist(state: &tauri::State<'_, AppState>, data: &AppData) -> Result<(), String> {
+    let data_path = lock_recover(&state.data_path).clone();
+    let max_backups = lock_recover(&state.settings).max_backups;
+    save_data(&data_path, data, max_backups).await
+}
+
 #[tauri::command]
-fn load_app_data(state: tauri::State<'_, AppState>) -> Result<AppData, String> {
-    let data = state.data.lock().map_err(|e| e.to_string())?;
-    Ok(data.clone())
+async fn load_app_data(state: tauri::State<'_, AppState>) -> Result<AppData, String> {
+    catch_async(async {
+        let data = lock_recover(&state.data);
+        Ok(data.clone())
+    })
+    .await
 }
 
 #[tauri::command]
-fn save_app_data(state: tauri::State<'_, AppState>, data: AppData) -> Result<(), String> {
-    let mut current = state.data.lock().map_err(|e| e.to_string())?;
-    *current = data.clone();
-    let data_path = state.data_path.lock().map_err(|e| e.to_string())?;
-    save_data(&data_path, &data)
+async fn save_app_data(state: tauri::State<'_, AppState>, data: AppData) -> Result<(), String> {
+    catch_async(async {
+        {
+            let mut current = lock_recover(&state.data);
+            *current = data.clone();
+        }
+        persist(&state, &data).await
+    })
+    .await
 }
 
 #[tauri::command]
-fn save_projects(state: tauri::State<'_, AppState>, projects: Vec<Project>) -> Result<(), String> {
-    let mut data = state.data.lock().map_err(|e| e.to_string())?;
-    data.projects = projects;
-    let data_path = state.data_path.lock().map_err(|e| e.to_string())?;
-    save_data(&data_path, &data)
+async fn save_projects(
+    state: tauri::State<'_, AppState>,
+    projects: Vec<Project>,
+) -> Result<(), String> {
+    catch_async(async {
+        let snapshot = {
+            let mut data = lock_recover(&state.data);
+            data.projects = projects;
+            data.clone()
+        };
+        persist(&state, &snapshot).await
+    })
+    .await
 }
 
 #[tauri::command]
-fn save_week(state: tauri::State<'_, AppState>, week: WeekData) -> Result<(), String> {
-    let mut data = state.data.lock().map_err(|e| e.to_string())?;
-    if let Some(existing) = data.weeks.iter_mut().find(|w| w.week_key == week.week_key) {
-        *existing = week;
-    } else {
-        data.weeks.push(week);
-    }
-    let data_path = state.data_path.lock().map_err(|e| e.to_string())?;
-    save_data(&data_path, &data)
+async fn save_week(state: tauri::State<'_, AppState>, week: WeekData) -> Result<(), String> {
+    catch_async(async {
+        let snapshot = {
+            let mut data = lock_recover(&state.data);
+            if let Some(existing) = data.weeks.iter_mut().find(|w| w.week_key == week.week_key) {
+                *existing = week;
+            } else {
+                data.weeks.push(week);
+            }
+            data.clone()
+        };
+        persist(&state, &snapshot).await
+    })
+    .await
 }
 
 #[tauri::command]
-fn save_templates(
+async fn save_templates(
     state: tauri::State<'_, AppState>,
     templates: Vec<Template>,
 ) -> Result<(), String> {
-    let mut data = state.data.lock().map_err(|e| e.to_string())?;
-    data.templates = templates;
-    let data_path = state.data_path.lock().map_err(|e| e.to_string())?;
-    save_data(&data_path, &data)
+    catch_async(async {
+        let snapshot = {
+            let mut data = lock_recover(&state.data);
+            data.templates = templates;
+            data.clone()
+        };
+        persist(&state, &snapshot).await
+    })
+    .await
+}
+
+#[tauri::command]
+async fn list_backups(state: tauri::State<'_, AppState>) -> Result<Vec<BackupInfo>, String> {
+    catch_async(async {
+        let data_path = lock_recover(&state.data_path).clone();
+        let dir = backups_dir(&data_path);
+        if fs::metadata(&dir).await.is_err() {
+            return Ok(Vec::new());
+        }
+
+        let original_file_name = data_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("time_manager_data.json");
+        let suffix = format!(".{}", original_file_name);
+
+        let mut entries = fs::read_dir(&dir).await.map_err(|e| e.to_string())?;
+        let mut backups = Vec::new();
+        while let Some(entry) = entries.next_entry().await.map_err(|e| e.to_string())? {
+            let meta = entry.metadata().await.map_err(|e| e.to_string())?;
+            if !meta.is_file() {
+                continue;
+            }
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let timestamp = file_name
+                .strip_suffix(suffix.as_str())
+                .map(|ts| ts.to_string())
+                .unwrap_or_else(|| file_name.clone());
+            backups.push(BackupInfo {
+                path: entry.path().to_string_lossy().to_string(),
+                timestamp,
+                size: meta.len(),
+            });
+        }
+        backups.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        Ok(backups)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn restore_backup(
+    state: tauri::State<'_, AppState>,
+    timestamp: String,
+) -> Result<(), String> {
+    catch_async(async {
+        let data_path = lock_recover(&state.data_path).clone();
+        let dir = backups_dir(&data_path);
+
+        let mut entries = fs::read_dir(&dir).await.map_err(|e| e.to_string())?;
+        let mut backup_path = None;
+        while let Some(entry) = entries.next_entry().await.map_err(|e| e.to_string())? {
+            if entry.file_name().to_string_lossy().starts_with(&timestamp) {
+                backup_path = Some(entry.path());
+                break;
+            }
+        }
+        let backup_path =
+            backup_path.ok_or_else(|| format!("No backup found for timestamp: {}", timestamp))?;
+
+        let contents = fs::read_to_string(&backup_path)
+            .await
+            .map_err(|e| e.to_string())?;
+        let mut value: serde_json::Value =
+            serde_json::from_str(&contents).map_err(|e| e.to_string())?;
+        let on_disk_version = check_schema_version(&value)?;
+
+        let max_backups = lock_recover(&state.settings).max_backups;
+        if on_disk_version < CURRENT_SCHEMA_VERSION {
+            // The backup predates a migration; run it through the same
+            // migrate-then-write path `load_data` uses instead of reviving
+            // its pre-migration shape as if it were current.
+            value = migrate_data(&data_path, value, on_disk_version, max_backups).await?;
+        } else {
+            rotate_backup(&data_path, max_backups).await?;
+            let json = serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?;
+            atomic_write(&data_path, &json).await?;
+        }
+
+        let restored: AppData = serde_json::from_value(value).map_err(|e| e.to_string())?;
+        let mut data = lock_recover(&state.data);
+        *data = restored;
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+fn list_profiles(state: tauri::State<'_, AppState>) -> Result<Vec<Profile>, String> {
+    catch_sync(|| {
+        let settings = lock_recover(&state.settings);
+        Ok(settings.profiles.clone())
+    })
+}
+
+#[tauri::command]
+async fn create_profile(
+    state: tauri::State<'_, AppState>,
+    name: String,
+    data_path: String,
+    weekly_hour_goal_override: Option<f64>,
+) -> Result<(), String> {
+    catch_async(async {
+        let dir = PathBuf::from(&data_path);
+        let meta = fs::metadata(&dir)
+            .await
+            .map_err(|_| format!("Directory does not exist: {}", data_path))?;
+        if !meta.is_dir() {
+            return Err(format!("Path is not a directory: {}", data_path));
+        }
+
+        let config_path = get_config_path(&state.app_handle).await;
+        let snapshot = {
+            let mut settings = lock_recover(&state.settings);
+            if settings.profiles.iter().any(|p| p.name == name) {
+                return Err(format!("Profile already exists: {}", name));
+            }
+            settings.profiles.push(Profile {
+                name,
+                data_path,
+                weekly_hour_goal_override,
+            });
+            settings.clone()
+        };
+        save_settings_file(&config_path, &snapshot).await
+    })
+    .await
+}
+
+#[tauri::command]
+async fn switch_profile(state: tauri::State<'_, AppState>, name: String) -> Result<(), String> {
+    catch_async(async {
+        let config_path = get_config_path(&state.app_handle).await;
+        let (profile, snapshot) = {
+            let mut settings = lock_recover(&state.settings);
+            let profile = settings
+                .profiles
+                .iter()
+                .find(|p| p.name == name)
+                .cloned()
+                .ok_or_else(|| format!("No such profile: {}", name))?;
+            settings.active_profile = Some(name);
+            (profile, settings.clone())
+        };
+        save_settings_file(&config_path, &snapshot).await?;
+
+        let new_path = PathBuf::from(&profile.data_path).join("time_manager_data.json");
+        let max_backups = snapshot.max_backups;
+        let loaded = load_data_with_path(new_path, max_backups).await?;
+        let mut value = loaded.value;
+        if let Some(goal) = profile.weekly_hour_goal_override {
+            value.weekly_hour_goal = goal;
+        }
+
+        {
+            let mut data_path = lock_recover(&state.data_path);
+            *data_path = loaded.path.clone();
+        }
+        let mut data = lock_recover(&state.data);
+        *data = value;
+        drop(data);
+        rewatch(state.inner(), &loaded.path);
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+async fn delete_profile(state: tauri::State<'_, AppState>, name: String) -> Result<(), String> {
+    catch_async(async {
+        let config_path = get_config_path(&state.app_handle).await;
+        let (snapshot, was_active) = {
+            let mut settings = lock_recover(&state.settings);
+            let before = settings.profiles.len();
+            settings.profiles.retain(|p| p.name != name);
+            if settings.profiles.len() == before {
+                return Err(format!("No such profile: {}", name));
+            }
+            let was_active = settings.active_profile.as_deref() == Some(name.as_str());
+            if was_active {
+                settings.active_profile = None;
+            }
+            (settings.clone(), was_active)
+        };
+        save_settings_file(&config_path, &snapshot).await?;
+
+        if was_active {
+            // The running app was reading/writing/watching the deleted
+            // profile's file; re-resolve the effective path from the updated
+            // settings (as startup does), then reload and rewatch it as
+            // switch_profile does, so it doesn't keep targeting a file that's
+            // no longer listed in settings.
+            let new_path = get_data_path(&state.app_handle, &snapshot).await;
+            let new_data = load_data(&new_path, snapshot.max_backups).await?;
+
+            {
+                let mut data_path = lock_recover(&state.data_path);
+                *data_path = new_path.clone();
+            }
+            let mut data = lock_recover(&state.data);
+            *data = new_data;
+            drop(data);
+            rewatch(state.inner(), &new_path);
+        }
+
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+async fn export_week_ics(
+    state: tauri::State<'_, AppState>,
+    week_key: String,
+) -> Result<String, String> {
+    catch_async(async {
+        let (week, projects, data_path) = {
+            let data = lock_recover(&state.data);
+            let week = data
+                .weeks
+                .iter()
+                .find(|w| w.week_key == week_key)
+                .cloned()
+                .ok_or_else(|| format!("No such week: {}", week_key))?;
+            let data_path = lock_recover(&state.data_path).clone();
+            (week, data.projects.clone(), data_path)
+        };
+
+        let dtstamp = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+
+        let mut ics = String::new();
+        ics.push_str("BEGIN:VCALENDAR\r\n");
+        ics.push_str("VERSION:2.0\r\n");
+        ics.push_str("PRODID:-//Time Manager//EN\r\n");
+        for block in &week.blocks {
+            let title = projects
+                .iter()
+                .find(|p| p.id == block.project_id)
+                .map(|p| p.name.clone())
+                .unwrap_or_else(|| "Untitled".to_string());
+            let start = slot_start(&week.start_date, &block.day, block.slot_index)?;
+            let end = start + chrono::Duration::minutes(SLOT_MINUTES);
+            ics.push_str("BEGIN:VEVENT\r\n");
+            ics.push_str(&format!("UID:{}@time-manager\r\n", block.id));
+            ics.push_str(&format!("DTSTAMP:{}\r\n", dtstamp));
+            ics.push_str(&format!("DTSTART:{}\r\n", start.format("%Y%m%dT%H%M%S")));
+            ics.push_str(&format!("DTEND:{}\r\n", end.format("%Y%m%dT%H%M%S")));
+            ics.push_str(&format!("SUMMARY:{}\r\n", ics_escape(&title)));
+            ics.push_str("END:VEVENT\r\n");
+        }
+        ics.push_str("END:VCALENDAR\r\n");
+
+        write_export(&data_path, &format!("{}.ics", week_key), &ics).await?;
+        Ok(ics)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn export_range_csv(
+    state: tauri::State<'_, AppState>,
+    start_week: String,
+    end_week: String,
+) -> Result<String, String> {
+    catch_async(async {
+        let (data, data_path) = {
+            let data = lock_recover(&state.data).clone();
+            let data_path = lock_recover(&state.data_path).clone();
+            (data, data_path)
+        };
+
+        let mut weeks: Vec<&WeekData> = data
+            .weeks
+            .iter()
+            .filter(|w| w.week_key.as_str() >= start_week.as_str() && w.week_key.as_str() <= end_week.as_str())
+            .collect();
+        weeks.sort_by(|a, b| a.week_key.cmp(&b.week_key));
+
+        let mut csv = String::from("Week,Day,Project,Start,End,Hours,ChargeCode,ChargeHours\n");
+        for week in weeks {
+            for block in &week.blocks {
+                let project = data.projects.iter().find(|p| p.id == block.project_id);
+                let project_name = project
+                    .map(|p| p.name.clone())
+                    .unwrap_or_else(|| "Untitled".to_string());
+                let start = slot_start(&week.start_date, &block.day, block.slot_index)?;
+                let end = start + chrono::Duration::minutes(SLOT_MINUTES);
+                let hours = SLOT_MINUTES as f64 / 60.0;
+
+                let splits = project
+                    .and_then(|p| p.charge_code_splits.clone())
+                    .unwrap_or_else(|| {
+                        vec![ChargeCodeSplit {
+                            code: "UNASSIGNED".to_string(),
+                            percentage: 100.0,
+                        }]
+                    });
+
+                for split in splits {
+                    let apportioned_hours = hours * split.percentage / 100.0;
+                    csv.push_str(&format!(
+                        "{},{},{},{},{},{:.2},{},{:.2}\n",
+                        week.week_key,
+                        block.day,
+                        csv_escape(&project_name),
+                        start.format("%H:%M"),
+                        end.format("%H:%M"),
+                        hours,
+                        csv_escape(&split.code),
+                        apportioned_hours,
+                    ));
+                }
+            }
+        }
+
+        write_export(
+            &data_path,
+            &format!("{}_{}.csv", start_week, end_week),
+            &csv,
+        )
+        .await?;
+        Ok(csv)
+    })
+    .await
 }
 
 #[tauri::command]
 fn get_data_location(state: tauri::State<'_, AppState>) -> Result<String, String> {
-    let data_path = state.data_path.lock().map_err(|e| e.to_string())?;
-    let parent = data_path
-        .parent()
-        .ok_or_else(|| "Failed to get parent directory".to_string())?;
-    Ok(parent.to_string_lossy().to_string())
+    catch_sync(|| {
+        let data_path = lock_recover(&state.data_path);
+        let parent = data_path
+            .parent()
+            .ok_or_else(|| "Failed to get parent directory".to_string())?;
+        Ok(parent.to_string_lossy().to_string())
+    })
 }
 
 #[tauri::command]
@@ -253,50 +1112,68 @@ async fn set_data_location(
     new_path: String,
     copy_existing: bool,
 ) -> Result<(), String> {
-    let new_dir = PathBuf::from(&new_path);
-    
-    // Validate the new path
-    if !new_dir.exists() {
-        return Err(format!("Directory does not exist: {}", new_path));
-    }
-    if !new_dir.is_dir() {
-        return Err(format!("Path is not a directory: {}", new_path));
-    }
-    
-    // Test if directory is writable
-    let test_file = new_dir.join(".test_write");
-    if let Err(e) = fs::write(&test_file, "test") {
-        return Err(format!("Directory is not writable: {}", e));
-    }
-    let _ = fs::remove_file(&test_file);
-    
-    let new_data_file = new_dir.join("time_manager_data.json");
-    
-    // Copy existing data if requested
-    if copy_existing {
-        let old_data_path = state.data_path.lock().map_err(|e| e.to_string())?;
-        if old_data_path.exists() && !new_data_file.exists() {
-            fs::copy(&*old_data_path, &new_data_file)
-                .map_err(|e| format!("Failed to copy data: {}", e))?;
+    catch_async(async {
+        let new_dir = PathBuf::from(&new_path);
+
+        // Validate the new path
+        let meta = fs::metadata(&new_dir)
+            .await
+            .map_err(|_| format!("Directory does not exist: {}", new_path))?;
+        if !meta.is_dir() {
+            return Err(format!("Path is not a directory: {}", new_path));
         }
-    }
-    
-    // Update settings
-    let config_path = get_config_path(&state.app_handle);
-    let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
-    settings.custom_data_path = Some(new_path);
-    save_settings_file(&config_path, &settings)?;
-    
-    // Update data path and reload data
-    let mut data_path = state.data_path.lock().map_err(|e| e.to_string())?;
-    *data_path = new_data_file.clone();
-    drop(data_path);
-    
-    let new_data = load_data(&new_data_file);
-    let mut data = state.data.lock().map_err(|e| e.to_string())?;
-    *data = new_data;
-    
-    Ok(())
+
+        // Test if directory is writable
+        let test_file = new_dir.join(".test_write");
+        if let Err(e) = fs::write(&test_file, "test").await {
+            return Err(format!("Directory is not writable: {}", e));
+        }
+        let _ = fs::remove_file(&test_file).await;
+
+        let new_data_file = new_dir.join("time_manager_data.json");
+
+        // Copy existing data if requested
+        if copy_existing {
+            let old_data_path = lock_recover(&state.data_path).clone();
+            if fs::metadata(&old_data_path).await.is_ok()
+                && fs::metadata(&new_data_file).await.is_err()
+            {
+                fs::copy(&old_data_path, &new_data_file)
+                    .await
+                    .map_err(|e| format!("Failed to copy data: {}", e))?;
+            }
+        }
+
+        // Update settings. Clearing the active profile here too, since an
+        // active profile's data path otherwise wins over custom_data_path on
+        // the next launch (see resolve_effective_config), which would make
+        // this relocation silently revert at restart.
+        let config_path = get_config_path(&state.app_handle).await;
+        {
+            let mut settings = lock_recover(&state.settings);
+            settings.custom_data_path = Some(new_path);
+            settings.active_profile = None;
+            let settings_snapshot = settings.clone();
+            drop(settings);
+            save_settings_file(&config_path, &settings_snapshot).await?;
+        }
+
+        // Update data path and reload data
+        {
+            let mut data_path = lock_recover(&state.data_path);
+            *data_path = new_data_file.clone();
+        }
+
+        let max_backups = lock_recover(&state.settings).max_backups;
+        let new_data = load_data(&new_data_file, max_backups).await?;
+        let mut data = lock_recover(&state.data);
+        *data = new_data;
+        drop(data);
+        rewatch(state.inner(), &new_data_file);
+
+        Ok(())
+    })
+    .await
 }
 
 #[tauri::command]
@@ -304,37 +1181,56 @@ async fn reset_to_default_location(
     state: tauri::State<'_, AppState>,
     copy_existing: bool,
 ) -> Result<(), String> {
-    let default_path = get_default_data_path(&state.app_handle);
-    
-    // Copy existing data if requested
-    if copy_existing {
-        let old_data_path = state.data_path.lock().map_err(|e| e.to_string())?;
-        if old_data_path.exists() && !default_path.exists() {
-            if let Some(parent) = default_path.parent() {
-                fs::create_dir_all(parent)
-                    .map_err(|e| format!("Failed to create directory: {}", e))?;
+    catch_async(async {
+        let default_path = get_default_data_path(&state.app_handle).await;
+
+        // Copy existing data if requested
+        if copy_existing {
+            let old_data_path = lock_recover(&state.data_path).clone();
+            if fs::metadata(&old_data_path).await.is_ok()
+                && fs::metadata(&default_path).await.is_err()
+            {
+                if let Some(parent) = default_path.parent() {
+                    fs::create_dir_all(parent)
+                        .await
+                        .map_err(|e| format!("Failed to create directory: {}", e))?;
+                }
+                fs::copy(&old_data_path, &default_path)
+                    .await
+                    .map_err(|e| format!("Failed to copy data: {}", e))?;
             }
-            fs::copy(&*old_data_path, &default_path)
-                .map_err(|e| format!("Failed to copy data: {}", e))?;
         }
-    }
-    
-    // Update settings
-    let config_path = get_config_path(&state.app_handle);
-    let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
-    settings.custom_data_path = None;
-    save_settings_file(&config_path, &settings)?;
-    
-    // Update data path and reload data
-    let mut data_path = state.data_path.lock().map_err(|e| e.to_string())?;
-    *data_path = default_path.clone();
-    drop(data_path);
-    
-    let new_data = load_data(&default_path);
-    let mut data = state.data.lock().map_err(|e| e.to_string())?;
-    *data = new_data;
-    
-    Ok(())
+
+        // Update settings. Clearing the active profile here too, since an
+        // active profile's data path otherwise wins over the default on the
+        // next launch (see resolve_effective_config), which would make this
+        // reset silently revert at restart.
+        let config_path = get_config_path(&state.app_handle).await;
+        {
+            let mut settings = lock_recover(&state.settings);
+            settings.custom_data_path = None;
+            settings.active_profile = None;
+            let settings_snapshot = settings.clone();
+            drop(settings);
+            save_settings_file(&config_path, &settings_snapshot).await?;
+        }
+
+        // Update data path and reload data
+        {
+            let mut data_path = lock_recover(&state.data_path);
+            *data_path = default_path.clone();
+        }
+
+        let max_backups = lock_recover(&state.settings).max_backups;
+        let new_data = load_data(&default_path, max_backups).await?;
+        let mut data = lock_recover(&state.data);
+        *data = new_data;
+        drop(data);
+        rewatch(state.inner(), &default_path);
+
+        Ok(())
+    })
+    .await
 }
 
 // ============================================================
@@ -343,21 +1239,32 @@ async fn reset_to_default_location(
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    tracing_subscriber::fmt::init();
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .setup(|app| {
             let handle = app.handle().clone();
-            let config_path = get_config_path(&handle);
-            let settings = load_settings(&config_path);
-            let data_path = get_data_path(&handle, &settings);
-            let data = load_data(&data_path);
+            let (settings, data_path, data) = tauri::async_runtime::block_on(async {
+                let config_path = get_config_path(&handle).await;
+                let settings = load_settings(&config_path).await;
+                let data_path = get_data_path(&handle, &settings).await;
+                let mut data = load_data(&data_path, settings.max_backups).await?;
+                if let Some(goal) = resolve_effective_config(&settings).weekly_hour_goal {
+                    data.weekly_hour_goal = goal;
+                }
+                Ok::<_, String>((settings, data_path, data))
+            })?;
             app.manage(AppState {
                 data: Mutex::new(data),
-                data_path: Mutex::new(data_path),
+                data_path: Mutex::new(data_path.clone()),
                 settings: Mutex::new(settings),
                 app_handle: handle,
+                watcher: Mutex::new(None),
+                reconcile_lock: tokio::sync::Mutex::new(()),
             });
+            rewatch(app.state::<AppState>().inner(), &data_path);
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -369,6 +1276,14 @@ pub fn run() {
             get_data_location,
             set_data_location,
             reset_to_default_location,
+            list_backups,
+            restore_backup,
+            list_profiles,
+            create_profile,
+            switch_profile,
+            delete_profile,
+            export_week_ics,
+            export_range_csv,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");